@@ -0,0 +1,602 @@
+use crate::{CommitState, Status};
+
+/// Parent edges between commits in the bisection range, indexed the same way
+/// as the `commits` slice passed to [`init`] (oldest first, so every
+/// commit's parents have a strictly smaller index).
+#[derive(Debug, Clone)]
+pub struct CommitGraph {
+    pub parents: Vec<Vec<usize>>,
+}
+
+impl CommitGraph {
+    fn children(&self, idx: usize) -> Vec<usize> {
+        self.parents
+            .iter()
+            .enumerate()
+            .filter(|(_, parents)| parents.contains(&idx))
+            .map(|(child, _)| child)
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Runners {
+    pub commits: Vec<usize>,
+    pub start_times: Vec<f64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct State {
+    pub commits: Vec<CommitState>,
+    pub runners: Runners,
+    pub graph: CommitGraph,
+    /// Indices that have been tested and come back `Status::Skip`, in the
+    /// order they were reported. [`State::skip_exhausted`] checks the
+    /// unresolved commits against this list.
+    pub skipped: Vec<usize>,
+    /// Observed wall-clock runtime of each commit's runner, once it has
+    /// completed. Lets the allocator favor splits expected to finish sooner.
+    pub runtimes: Vec<Option<f64>>,
+    /// `ancestor_sets[i]` = indices of `i` and all its ancestors, per the
+    /// (static) commit graph. The graph never changes across a bisection, so
+    /// this is computed once in [`init`] and carried forward unchanged by
+    /// [`step`].
+    pub ancestor_sets: Vec<Vec<usize>>,
+    /// `descendant_sets[i]` = indices of `i` and all its descendants, the
+    /// inverse of `ancestor_sets`. Also static for the life of a bisection;
+    /// [`step`] uses it to find exactly which commits' weights are touched
+    /// when a commit resolves, instead of rescanning every commit.
+    pub descendant_sets: Vec<Vec<usize>>,
+    /// `weights[i]` = count of unresolved commits among `i` and its
+    /// ancestors. Computed once in [`init`] and then updated incrementally by
+    /// [`step`]: when a commit resolves to `Good`/`Bad`, only the weights of
+    /// its descendants (the affected subgraph, via `descendant_sets`) are
+    /// decremented, rather than recounting every commit's ancestor set.
+    pub weights: Vec<usize>,
+}
+
+impl State {
+    /// True once every unresolved commit in the narrowed range has been
+    /// tested and skipped, so no further step can make progress.
+    pub fn skip_exhausted(&self) -> bool {
+        let unresolved = unresolved_indices(&self.commits);
+        !unresolved.is_empty() && unresolved.iter().all(|i| self.skipped.contains(i))
+    }
+
+    /// The commits that could still be the first bad one: every commit in
+    /// the narrowed range that is neither confirmed good nor confirmed bad.
+    pub fn suspects(&self) -> Vec<&CommitState> {
+        unresolved_indices(&self.commits)
+            .into_iter()
+            .map(|i| &self.commits[i])
+            .collect()
+    }
+}
+
+/// Commits still in play: not yet resolved to `Good`/`Bad`. Includes `Skip`,
+/// since a skipped commit remains a suspect, just an untestable one.
+fn unresolved_indices(commits: &[CommitState]) -> Vec<usize> {
+    (0..commits.len())
+        .filter(|&i| matches!(commits[i].status, Status::Unknown | Status::Skip))
+        .collect()
+}
+
+/// Git-style fallback for when the ideal split point has already been tested
+/// and skipped (or already has a runner assigned to it, per `taken`): walk
+/// outward from `start`, alternating `start+1, start-1, start+2, start-2,
+/// ...`, and return the first commit that is both unknown and not in
+/// `taken`. Returns `None` if every commit in range is resolved, skipped, or
+/// already has a runner.
+fn probe_nearest_untested(commits: &[CommitState], start: usize, taken: &[usize]) -> Option<usize> {
+    let available = |i: usize| commits[i].status == Status::Unknown && !taken.contains(&i);
+
+    if available(start) {
+        return Some(start);
+    }
+
+    let len = commits.len();
+    for offset in 1..len {
+        if start + offset < len && available(start + offset) {
+            return Some(start + offset);
+        }
+        if offset <= start && available(start - offset) {
+            return Some(start - offset);
+        }
+    }
+    None
+}
+
+/// Estimated runtime for commit `idx`: its own measured runtime if known,
+/// otherwise the nearest neighbor's (by index) measured runtime, so the
+/// allocator can bias towards commits whose build is expected to finish
+/// quickly even before that exact commit has ever been run.
+fn estimated_runtime(state: &State, idx: usize) -> Option<f64> {
+    if let Some(runtime) = state.runtimes[idx] {
+        return Some(runtime);
+    }
+
+    let len = state.runtimes.len();
+    for offset in 1..len {
+        if idx + offset < len {
+            if let Some(runtime) = state.runtimes[idx + offset] {
+                return Some(runtime);
+            }
+        }
+        if offset <= idx {
+            if let Some(runtime) = state.runtimes[idx - offset] {
+                return Some(runtime);
+            }
+        }
+    }
+    None
+}
+
+pub trait Allocator {
+    /// Choose up to `n` still-unknown commits to hand runners to.
+    fn allocate(state: &State, n: usize) -> Vec<usize>;
+}
+
+/// Bisects by DAG weight rather than array midpoint: for each unresolved
+/// commit `X` (unknown or skipped), `weight(X)` is the number of unresolved
+/// commits in `ancestors(X) ∪ {X}`, `N` is the number of unresolved commits,
+/// and the allocator prefers commits maximizing `min(weight(X), N - weight(X))`
+/// so a verdict on `X` removes as close to half the remaining candidates as
+/// possible, mirroring git's bisection-weight algorithm.
+pub struct BasicAllocator;
+
+impl BasicAllocator {
+    /// `commits` is topologically ordered (parents always precede children),
+    /// so a single forward pass unions each commit's parents' ancestor sets
+    /// into its own. Computed once per bisection in [`init`] since the graph
+    /// never changes; callers should reuse [`State::ancestor_sets`] rather
+    /// than invoking this again.
+    fn compute_ancestor_sets(graph: &CommitGraph) -> Vec<Vec<usize>> {
+        let n = graph.parents.len();
+        let mut ancestors: Vec<Vec<bool>> = vec![vec![false; n]; n];
+
+        for i in 0..n {
+            ancestors[i][i] = true;
+            for &p in &graph.parents[i] {
+                let parent_ancestors = ancestors[p].clone();
+                for (j, is_ancestor) in parent_ancestors.into_iter().enumerate() {
+                    if is_ancestor {
+                        ancestors[i][j] = true;
+                    }
+                }
+            }
+        }
+
+        ancestors
+            .into_iter()
+            .map(|set| {
+                set.into_iter()
+                    .enumerate()
+                    .filter_map(|(j, is_ancestor)| is_ancestor.then_some(j))
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Inverse of `ancestor_sets`: `descendants[i]` = every `j` with `i` in
+    /// `ancestor_sets[j]`.
+    fn compute_descendant_sets(ancestor_sets: &[Vec<usize>]) -> Vec<Vec<usize>> {
+        let mut descendants = vec![Vec::new(); ancestor_sets.len()];
+        for (j, ancestors) in ancestor_sets.iter().enumerate() {
+            for &i in ancestors {
+                descendants[i].push(j);
+            }
+        }
+        descendants
+    }
+
+    /// Initial weights, for a freshly-init'd state where every commit is
+    /// still unknown: `weight[i]` is simply the size of `i`'s ancestor set,
+    /// with no need to check status yet. Later weight changes go through
+    /// [`apply_resolution`] instead of calling this again.
+    fn initial_weights(ancestor_sets: &[Vec<usize>]) -> Vec<usize> {
+        ancestor_sets.iter().map(Vec::len).collect()
+    }
+
+    /// A commit just resolved from `Unknown`/`Skip` to a genuine verdict
+    /// (`Good`/`Bad`), so it no longer counts toward anyone's weight: every
+    /// commit that had it in their ancestor set (its descendants, via the
+    /// cached `descendant_sets`) loses one from theirs. This touches only the
+    /// affected subgraph instead of recounting the whole state.
+    fn apply_resolution(weights: &mut [usize], descendant_sets: &[Vec<usize>], resolved: usize) {
+        for &d in &descendant_sets[resolved] {
+            weights[d] -= 1;
+        }
+    }
+}
+
+impl Allocator for BasicAllocator {
+    fn allocate(state: &State, n: usize) -> Vec<usize> {
+        let unresolved = unresolved_indices(&state.commits);
+        if unresolved.is_empty() || n == 0 {
+            return Vec::new();
+        }
+
+        // `total` reflects the full unknown set for the balance score, but a
+        // commit that already has a runner assigned must never be picked
+        // again - that would waste a job slot on a duplicate runner and leak
+        // a second completion message for an index the main loop already
+        // considers spoken for.
+        let total = unresolved.len();
+        let weight = &state.weights;
+        let candidates: Vec<usize> = unresolved
+            .into_iter()
+            .filter(|i| !state.runners.commits.contains(i))
+            .collect();
+        if candidates.is_empty() {
+            return Vec::new();
+        }
+
+        // Primarily rank by how close to an even split testing this commit
+        // would be; break ties by preferring commits with the shortest
+        // estimated build time, so measured runtimes shape scheduling
+        // instead of being discarded once observed.
+        let mut ranked = candidates;
+        ranked.sort_by(|&a, &b| {
+            let balance_a = weight[a].min(total - weight[a]);
+            let balance_b = weight[b].min(total - weight[b]);
+            balance_b.cmp(&balance_a).then_with(|| {
+                let runtime_a = estimated_runtime(state, a).unwrap_or(f64::INFINITY);
+                let runtime_b = estimated_runtime(state, b).unwrap_or(f64::INFINITY);
+                runtime_a.partial_cmp(&runtime_b).unwrap_or(std::cmp::Ordering::Equal)
+            })
+        });
+
+        // The weight-ideal commit may already be a skipped one, since skip
+        // verdicts don't remove a commit from suspicion, only from
+        // testability; probe outward for the nearest commit we can actually
+        // run before falling through to the next-best-weighted split.
+        let mut picks = Vec::with_capacity(n);
+        for &ideal in &ranked {
+            if picks.len() >= n {
+                break;
+            }
+            if let Some(candidate) = probe_nearest_untested(&state.commits, ideal, &state.runners.commits) {
+                if !picks.contains(&candidate) {
+                    picks.push(candidate);
+                }
+            }
+        }
+        picks
+    }
+}
+
+pub fn init(commits: &[String], parents: &[Vec<usize>], jobs: usize, check_bounds: bool) -> State {
+    let commit_states = commits
+        .iter()
+        .map(|hash| CommitState {
+            hash: hash.clone(),
+            status: Status::Unknown,
+        })
+        .collect();
+
+    let graph = CommitGraph {
+        parents: parents.to_vec(),
+    };
+    let ancestor_sets = BasicAllocator::compute_ancestor_sets(&graph);
+    let descendant_sets = BasicAllocator::compute_descendant_sets(&ancestor_sets);
+    let weights = BasicAllocator::initial_weights(&ancestor_sets);
+
+    let mut state = State {
+        commits: commit_states,
+        runners: Runners {
+            commits: Vec::new(),
+            start_times: Vec::new(),
+        },
+        graph,
+        skipped: Vec::new(),
+        runtimes: vec![None; commits.len()],
+        ancestor_sets,
+        descendant_sets,
+        weights,
+    };
+
+    let last = state.commits.len().saturating_sub(1);
+    let mut picks = Vec::new();
+    if check_bounds {
+        picks.push(0);
+        if last != 0 {
+            picks.push(last);
+        }
+    }
+
+    let remaining = jobs.saturating_sub(picks.len());
+    for c in BasicAllocator::allocate(&state, remaining) {
+        if !picks.contains(&c) {
+            picks.push(c);
+        }
+    }
+
+    state.runners.start_times = vec![0.0; picks.len()];
+    state.runners.commits = picks;
+    state
+}
+
+pub fn step<A: Allocator>(
+    state: &State,
+    result: Status,
+    commit_idx: usize,
+    commit_runtime: f64,
+    current_runtime: f64,
+) -> (State, Vec<usize>, Vec<usize>) {
+    let mut commits = state.commits.clone();
+    commits[commit_idx].status = result;
+    let narrowed = narrow_bounds(&mut commits, &state.graph, commit_idx, result);
+
+    let mut skipped = state.skipped.clone();
+    if result == Status::Skip && !skipped.contains(&commit_idx) {
+        skipped.push(commit_idx);
+    }
+
+    let mut runtimes = state.runtimes.clone();
+    runtimes[commit_idx] = Some(commit_runtime);
+
+    // `commit_idx` plus everything `narrow_bounds` just narrowed moved from
+    // Unknown/Skip to a genuine verdict this step; update only the affected
+    // subgraph's weights instead of recounting the whole state.
+    let mut weights = state.weights.clone();
+    if matches!(result, Status::Good | Status::Bad) {
+        BasicAllocator::apply_resolution(&mut weights, &state.descendant_sets, commit_idx);
+    }
+    for &idx in &narrowed {
+        BasicAllocator::apply_resolution(&mut weights, &state.descendant_sets, idx);
+    }
+
+    let invalidated: Vec<usize> = state
+        .runners
+        .commits
+        .iter()
+        .copied()
+        .filter(|&i| i != commit_idx && commits[i].status != Status::Unknown)
+        .collect();
+
+    let surviving: Vec<usize> = state
+        .runners
+        .commits
+        .iter()
+        .copied()
+        .filter(|&i| i != commit_idx && !invalidated.contains(&i))
+        .collect();
+
+    let probe = State {
+        commits: commits.clone(),
+        runners: Runners {
+            commits: surviving.clone(),
+            start_times: Vec::new(),
+        },
+        graph: state.graph.clone(),
+        skipped: skipped.clone(),
+        runtimes: runtimes.clone(),
+        ancestor_sets: state.ancestor_sets.clone(),
+        descendant_sets: state.descendant_sets.clone(),
+        weights: weights.clone(),
+    };
+    let slots = state.runners.commits.len() - surviving.len();
+    let new_runners: Vec<usize> = A::allocate(&probe, slots);
+
+    let mut start_times = Vec::with_capacity(surviving.len() + new_runners.len());
+    for &c in &surviving {
+        let idx = state.runners.commits.iter().position(|&x| x == c).unwrap();
+        start_times.push(state.runners.start_times[idx]);
+    }
+    start_times.extend(std::iter::repeat_n(current_runtime, new_runners.len()));
+
+    let mut runner_commits = surviving;
+    runner_commits.extend(new_runners.iter().copied());
+
+    let new_state = State {
+        commits,
+        runners: Runners {
+            commits: runner_commits,
+            start_times,
+        },
+        graph: state.graph.clone(),
+        skipped,
+        runtimes,
+        ancestor_sets: state.ancestor_sets.clone(),
+        descendant_sets: state.descendant_sets.clone(),
+        weights,
+    };
+
+    (new_state, invalidated, new_runners)
+}
+
+/// A good verdict on `idx` makes every ancestor good too; a bad verdict makes
+/// every descendant bad too. Narrowing these eagerly keeps the unknown set as
+/// small as possible between steps. Returns the indices actually flipped, so
+/// the caller can update their weights without rescanning the whole state.
+fn narrow_bounds(commits: &mut [CommitState], graph: &CommitGraph, idx: usize, result: Status) -> Vec<usize> {
+    let mut narrowed = Vec::new();
+    match result {
+        Status::Good => {
+            let mut stack = graph.parents[idx].clone();
+            while let Some(p) = stack.pop() {
+                if commits[p].status == Status::Unknown {
+                    commits[p].status = Status::Good;
+                    narrowed.push(p);
+                    stack.extend(graph.parents[p].iter().copied());
+                }
+            }
+        }
+        Status::Bad => {
+            let mut stack = graph.children(idx);
+            while let Some(c) = stack.pop() {
+                if commits[c].status == Status::Unknown {
+                    commits[c].status = Status::Bad;
+                    narrowed.push(c);
+                    stack.extend(graph.children(c));
+                }
+            }
+        }
+        _ => {}
+    }
+    narrowed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn commits(n: usize) -> Vec<String> {
+        (0..n).map(|i| format!("c{i}")).collect()
+    }
+
+    // 0 -> 1 -> 2 -> 4
+    //       \-> 3 -/
+    // i.e. a linear chain with a merge at 4.
+    fn merge_parents() -> Vec<Vec<usize>> {
+        vec![vec![], vec![0], vec![1], vec![1], vec![2, 3]]
+    }
+
+    #[test]
+    fn weights_count_unresolved_ancestors() {
+        let state = init(&commits(5), &merge_parents(), 0, false);
+        // Every commit is still unknown, so weight[i] = |ancestors(i) ∪ {i}|.
+        assert_eq!(state.weights, vec![1, 2, 3, 3, 5]);
+    }
+
+    #[test]
+    fn weights_shrink_as_commits_resolve() {
+        let state = init(&commits(5), &merge_parents(), 0, false);
+        let (state, _, _) = step::<BasicAllocator>(&state, Status::Good, 0, 1.0, 1.0);
+        let (state, _, _) = step::<BasicAllocator>(&state, Status::Good, 1, 1.0, 1.0);
+        // 0 and 1 are resolved, so they no longer count toward anyone's weight.
+        assert_eq!(state.weights, vec![0, 0, 1, 1, 3]);
+    }
+
+    #[test]
+    fn narrow_bounds_good_propagates_to_all_ancestors() {
+        let mut commit_states: Vec<CommitState> = commits(5)
+            .into_iter()
+            .map(|hash| CommitState { hash, status: Status::Unknown })
+            .collect();
+        let graph = CommitGraph { parents: merge_parents() };
+
+        narrow_bounds(&mut commit_states, &graph, 2, Status::Good);
+
+        assert_eq!(commit_states[0].status, Status::Good);
+        assert_eq!(commit_states[1].status, Status::Good);
+        assert_eq!(commit_states[2].status, Status::Unknown); // idx itself untouched
+        assert_eq!(commit_states[3].status, Status::Unknown); // sibling branch unaffected
+        assert_eq!(commit_states[4].status, Status::Unknown);
+    }
+
+    #[test]
+    fn narrow_bounds_bad_propagates_to_all_descendants() {
+        let mut commit_states: Vec<CommitState> = commits(5)
+            .into_iter()
+            .map(|hash| CommitState { hash, status: Status::Unknown })
+            .collect();
+        let graph = CommitGraph { parents: merge_parents() };
+
+        narrow_bounds(&mut commit_states, &graph, 3, Status::Bad);
+
+        assert_eq!(commit_states[3].status, Status::Unknown); // idx itself untouched
+        assert_eq!(commit_states[4].status, Status::Bad);
+        assert_eq!(commit_states[0].status, Status::Unknown);
+        assert_eq!(commit_states[1].status, Status::Unknown);
+        assert_eq!(commit_states[2].status, Status::Unknown);
+    }
+
+    #[test]
+    fn probe_nearest_untested_prefers_start_then_alternates_outward() {
+        let commit_states: Vec<CommitState> = vec![
+            Status::Unknown,
+            Status::Good,
+            Status::Skip,
+            Status::Unknown,
+            Status::Bad,
+        ]
+        .into_iter()
+        .enumerate()
+        .map(|(i, status)| CommitState { hash: format!("c{i}"), status })
+        .collect();
+
+        // idx 2 is skipped (not available), so probing from 2 should walk
+        // outward and land on 3 before 0.
+        assert_eq!(probe_nearest_untested(&commit_states, 2, &[]), Some(3));
+        // idx 0 is itself unknown and available.
+        assert_eq!(probe_nearest_untested(&commit_states, 0, &[]), Some(0));
+    }
+
+    #[test]
+    fn probe_nearest_untested_returns_none_when_all_resolved_or_skipped() {
+        let commit_states: Vec<CommitState> = vec![Status::Good, Status::Skip, Status::Bad]
+            .into_iter()
+            .enumerate()
+            .map(|(i, status)| CommitState { hash: format!("c{i}"), status })
+            .collect();
+
+        assert_eq!(probe_nearest_untested(&commit_states, 1, &[]), None);
+    }
+
+    #[test]
+    fn skip_exhausted_true_only_when_every_unresolved_commit_is_skipped() {
+        let mut state = init(&commits(3), &[vec![], vec![0], vec![1]], 0, false);
+        state.commits[0].status = Status::Good;
+        state.commits[1].status = Status::Skip;
+        state.skipped = vec![1];
+        // idx 2 is still genuinely unknown (never run), so not exhausted yet.
+        assert!(!state.skip_exhausted());
+
+        state.commits[2].status = Status::Skip;
+        state.skipped = vec![1, 2];
+        assert!(state.skip_exhausted());
+    }
+
+    #[test]
+    fn skip_exhausted_false_once_fully_resolved() {
+        let mut state = init(&commits(2), &[vec![], vec![0]], 0, false);
+        state.commits[0].status = Status::Good;
+        state.commits[1].status = Status::Bad;
+        assert!(!state.skip_exhausted());
+    }
+
+    #[test]
+    fn suspects_includes_unknown_and_skipped_but_not_resolved() {
+        let mut state = init(&commits(4), &[vec![], vec![0], vec![1], vec![2]], 0, false);
+        state.commits[0].status = Status::Good;
+        state.commits[1].status = Status::Skip;
+        state.commits[3].status = Status::Bad;
+
+        let suspects: Vec<&str> = state.suspects().iter().map(|c| c.hash.as_str()).collect();
+        assert_eq!(suspects, vec!["c1", "c2"]);
+    }
+
+    #[test]
+    fn probe_nearest_untested_skips_commits_with_active_runners() {
+        let commit_states: Vec<CommitState> = vec![Status::Unknown; 3]
+            .into_iter()
+            .enumerate()
+            .map(|(i, status)| CommitState { hash: format!("c{i}"), status })
+            .collect();
+
+        // idx 1 is unknown but already has a runner (`taken`), so it must be
+        // skipped in favor of its nearest free neighbor (checked outward
+        // `start+1` before `start-1`).
+        assert_eq!(probe_nearest_untested(&commit_states, 1, &[1]), Some(2));
+    }
+
+    #[test]
+    fn allocate_never_repicks_a_commit_with_an_active_runner() {
+        let mut state = init(&commits(5), &merge_parents(), 0, false);
+        // Commit 4 already has a runner in flight.
+        state.runners.commits = vec![4];
+
+        let picks = BasicAllocator::allocate(&state, 5);
+
+        assert!(!picks.contains(&4));
+    }
+
+    #[test]
+    fn allocate_returns_empty_when_every_unresolved_commit_has_a_runner() {
+        let mut state = init(&commits(3), &[vec![], vec![0], vec![1]], 0, false);
+        state.runners.commits = vec![0, 1, 2];
+
+        assert_eq!(BasicAllocator::allocate(&state, 2), Vec::<usize>::new());
+    }
+}
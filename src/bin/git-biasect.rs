@@ -1,21 +1,20 @@
 use argh::FromArgs;
 use git_biasect::alloc::{init, step, BasicAllocator};
 use git_biasect::shell::{
-    bisect_report, get_commit_files, get_commits, reproducer_shell_commands, run_script,
-    worktree_prune,
+    bisect_report, bisect_terms, get_commit_files, get_commit_parents, get_commits, is_ancestor,
+    merge_base, read_cmd_out, reproducer_shell_commands, run_script, worktree_prune,
 };
 use git_biasect::visualize::print_commits;
-use git_biasect::{CommitState, Status};
-use rand::seq::IteratorRandom;
-use rand::{rngs::StdRng, SeedableRng};
+use git_biasect::{CommitState, Status, Terms};
 use std::collections::HashSet;
 use std::fs;
 use std::os::unix::process::ExitStatusExt;
 use std::path::{Path, PathBuf};
-use std::process::Child;
+use std::process::{Command, ExitStatus};
 use std::str;
-use std::thread::sleep;
-use std::time::{Duration, Instant};
+use std::sync::mpsc;
+use std::thread;
+use std::time::Instant;
 
 /**
 Git Biasect
@@ -45,6 +44,22 @@ struct RunOptions {
     #[argh(switch, short = 'r')]
     reckless: bool,
 
+    /// restrict bisection to the first-parent chain of the bad commit, to find which merge introduced a regression
+    #[argh(switch)]
+    first_parent: bool,
+
+    /// print each runner's captured stdout/stderr as it completes
+    #[argh(switch, short = 'v')]
+    verbose: bool,
+
+    /// word to use in place of "good", mirroring git's --term-old
+    #[argh(option, default = "String::from(\"good\")")]
+    term_old: String,
+
+    /// word to use in place of "bad", mirroring git's --term-new
+    #[argh(option, default = "String::from(\"bad\")")]
+    term_new: String,
+
     /// set the current working directory
     #[argh(option, short = 'C', default = "PathBuf::from(\".\")")]
     repo_path: PathBuf,
@@ -62,32 +77,70 @@ struct NextOptions {
     #[argh(switch, short = 'c')]
     check_bounds: bool,
 
+    /// word to use in place of "good", mirroring git's --term-old
+    #[argh(option, default = "String::from(\"good\")")]
+    term_old: String,
+
+    /// word to use in place of "bad", mirroring git's --term-new
+    #[argh(option, default = "String::from(\"bad\")")]
+    term_new: String,
+
     /// set the current working directory
     #[argh(option, short = 'C', default = "PathBuf::from(\".\")")]
     repo_path: PathBuf,
 }
 
+/// A runner in flight: its commit, the pid we can signal to cancel it, and
+/// when it was started (for computing its eventual runtime).
+struct Runner {
+    commit_idx: usize,
+    pid: u32,
+    start_time: f64,
+}
+
+/// Spawn each runner and, instead of polling, hand a thread that blocks on
+/// `Child::wait` and reports completion over `tx` the moment it happens.
 fn start_runners(
     runner_commits: &[usize],
     commits: &[String],
     repo_path: &PathBuf,
     script_path: &str,
-) -> Vec<(usize, Child)> {
+    tx: &mpsc::Sender<(usize, ExitStatus)>,
+    start_time: f64,
+) -> Vec<Runner> {
     runner_commits
         .iter()
         .map(|commit_idx| {
-            (
-                *commit_idx,
-                run_script(
-                    &fs::canonicalize(repo_path).unwrap(),
-                    script_path,
-                    commits.get(*commit_idx).unwrap(),
-                ),
-            )
+            let mut child = run_script(
+                &fs::canonicalize(repo_path).unwrap(),
+                script_path,
+                commits.get(*commit_idx).unwrap(),
+            );
+            let pid = child.id();
+            let commit_idx = *commit_idx;
+            let tx = tx.clone();
+            thread::spawn(move || {
+                let status = child.wait().expect("failed to wait on runner");
+                let _ = tx.send((commit_idx, status));
+            });
+
+            Runner {
+                commit_idx,
+                pid,
+                start_time,
+            }
         })
         .collect()
 }
 
+/// Cancel an in-flight runner by pid. Its wait-thread still sends its exit
+/// status on the shared channel once the process dies; callers must also
+/// record `commit_idx` as cancelled so the main loop's receive discards that
+/// stale completion instead of acting on it.
+fn kill_runner(pid: u32) {
+    let _ = Command::new("kill").arg("-9").arg(pid.to_string()).status();
+}
+
 fn bounds_validated(commits: &Vec<CommitState>, reckless_mode: bool) -> bool {
     if reckless_mode || commits.is_empty() {
         return true;
@@ -98,10 +151,10 @@ fn bounds_validated(commits: &Vec<CommitState>, reckless_mode: bool) -> bool {
     commit_states.contains(&Status::Good) && commit_states.contains(&Status::Bad)
 }
 
-fn bisect_report_all(commits: &Vec<CommitState>, repo_path: &Path) {
+fn bisect_report_all(commits: &Vec<CommitState>, repo_path: &Path, terms: &Terms) {
     for commit in commits {
         if commit.status != Status::Unknown {
-            bisect_report(repo_path, &commit.status, &commit.hash);
+            bisect_report(repo_path, &commit.status, &commit.hash, terms);
         }
     }
 }
@@ -111,29 +164,110 @@ fn main() -> Result<(), String> {
 
     match args.subcommand {
         SubCommands::Run(run_opts) => {
-            let commits = get_commits(&run_opts.repo_path)?;
+            let commits = get_commits(&run_opts.repo_path, run_opts.first_parent)?;
+            let terms = Terms {
+                old: run_opts.term_old.clone(),
+                new: run_opts.term_new.clone(),
+            };
+
+            // Pre-flight: bisect_report below shells out to `git bisect <verb>`, which
+            // rejects a verb that doesn't match the vocabulary the running `git bisect`
+            // session was actually started with (`git bisect terms`). Catch that mismatch
+            // up front instead of discovering it mid-run when `bisect_report`'s swallowed
+            // `git bisect old/new` failures silently stop updating the real bisect state.
+            let session_terms = bisect_terms(&run_opts.repo_path)?;
+            if session_terms.old != terms.old || session_terms.new != terms.new {
+                eprintln!(
+                    "--term-old/--term-new ({}/{}) don't match the vocabulary this `git bisect` \
+                    session was started with ({}/{}).\n\
+                    \n\
+                    Re-run git-biasect with the session's existing terms to keep your progress:\n\
+                    \n\
+                    \tgit-biasect run --term-old={} --term-new={} ...\n\
+                    \n\
+                    Or, to use {}/{} instead, start a new session (this drops any {}/{} \
+                    verdicts already recorded):\n\
+                    \n\
+                    \tgit bisect start --term-old={} --term-new={}",
+                    terms.old,
+                    terms.new,
+                    session_terms.old,
+                    session_terms.new,
+                    session_terms.old,
+                    session_terms.new,
+                    terms.old,
+                    terms.new,
+                    session_terms.old,
+                    session_terms.new,
+                    terms.old,
+                    terms.new,
+                );
+                return Ok(());
+            }
+
+            // Pre-flight: refuse to start if good isn't actually an ancestor of bad, since
+            // the allocator and narrowing logic both assume a single coherent DAG range.
+            let good = commits.first().ok_or("no commits to bisect")?.clone();
+            let bad = commits.last().ok_or("no commits to bisect")?.clone();
+            if !is_ancestor(&run_opts.repo_path, &good, &bad)? {
+                let bases = merge_base(&run_opts.repo_path, &good, &bad)?;
+                eprintln!(
+                    "{old} commit `{good}` is not an ancestor of {new} commit `{bad}`.\n\
+                    This usually means the {new} behavior predates the range you gave git-biasect.\n\
+                    \n\
+                    Merge base(s) of {old} and {new}:\n\
+                    {}\n\
+                    \n\
+                    Test a merge base directly before bisecting, e.g.:\n\
+                    {}",
+                    bases
+                        .iter()
+                        .map(|base| format!("  {base}"))
+                        .collect::<Vec<_>>()
+                        .join("\n"),
+                    bases
+                        .iter()
+                        .map(|base| reproducer_shell_commands(
+                            &run_opts.repo_path,
+                            &run_opts.script,
+                            base
+                        ))
+                        .collect::<Vec<_>>()
+                        .join("\n"),
+                    old = terms.old,
+                    new = terms.new,
+                );
+                return Ok(());
+            }
+
+            let parents = get_commit_parents(&run_opts.repo_path, &commits, run_opts.first_parent)?;
             let _files_per_commit = commits
                 .iter()
                 .map(|hash| get_commit_files(&run_opts.repo_path, hash).unwrap())
                 .collect::<Vec<_>>();
 
-            let mut state = init(&commits, run_opts.jobs, !run_opts.reckless);
-            let mut runners;
+            let mut state = init(&commits, &parents, run_opts.jobs, !run_opts.reckless);
+            let (tx, rx) = mpsc::channel::<(usize, ExitStatus)>();
 
             // Kick off runners
             let start = Instant::now();
-            runners = start_runners(
+            let mut runners: Vec<Runner> = start_runners(
                 &state.runners.commits,
                 &commits,
                 &run_opts.repo_path,
                 &run_opts.script,
+                &tx,
+                start.elapsed().as_secs_f64(),
             );
 
-            let mut loop_iter = 0;
-            loop {
-                loop_iter += 1;
-                let mut rng = StdRng::seed_from_u64(loop_iter);
+            // Commit indices whose runner was killed because another verdict
+            // already resolved their status. Their wait-thread still sends a
+            // completion on `tx` the moment the killed process actually
+            // exits, so `rx.recv()` below must discard any message matching
+            // a cancelled index instead of treating it as a live verdict.
+            let mut cancelled: HashSet<usize> = HashSet::new();
 
+            loop {
                 print_commits(
                     state
                         .commits
@@ -142,49 +276,86 @@ fn main() -> Result<(), String> {
                         .collect::<Vec<_>>()
                         .as_slice(),
                     &state.runners.commits,
+                    &terms,
                 );
 
-                // Wait for the first completed child
-                let mut first_completed = None;
-
-                // Runner count doesn't update
-                let runners_count = runners.len();
-                while first_completed.is_none() {
-                    for child in runners.iter_mut().choose_multiple(&mut rng, runners_count) {
-                        let res = child.1.try_wait();
-                        let res = res.unwrap();
-                        if let Some(exit_status) = res {
-                            first_completed = Some((child.0, exit_status));
-                        }
+                // Block until a runner thread reports completion - no polling, zero idle compute.
+                // Discard messages from runners that were cancelled out from under us; their
+                // wait-thread sends on this same channel regardless of whether anyone still cares.
+                let commit_index_exit_code = loop {
+                    let msg = rx.recv().expect("all runner threads disconnected");
+                    if cancelled.remove(&msg.0) {
+                        continue;
                     }
-                    // TODO: Replace with condvar or learn from the bisection script runtime to reduce compute burden
-                    sleep(Duration::from_secs(1));
+                    break msg;
+                };
+                let commit_hash = commits.get(commit_index_exit_code.0).unwrap();
+                let last_cmd_out = read_cmd_out(
+                    &run_opts.repo_path,
+                    &run_opts.script,
+                    commit_hash,
+                    commit_index_exit_code.1.code(),
+                );
+                if run_opts.verbose {
+                    println!("{last_cmd_out}");
                 }
 
-                let commit_index_exit_code = first_completed.unwrap();
-                let exit_code = commit_index_exit_code
-                    .1
-                    .code()
-                    .or_else(|| commit_index_exit_code.1.signal())
-                    .unwrap();
-                let exit_status = if exit_code == 0 {
-                    Status::Good
-                } else if exit_code == 124 {
-                    Status::Skip
-                } else {
-                    Status::Bad
+                // Mirror git's exit-code convention: 0 is good, 125 (124 kept as an alias
+                // since scripts commonly wrap in `timeout`) is skip, and 1..125 is a real
+                // bad verdict. Codes 126/127 (command not found/not executable), 128+, and
+                // signal termination (`.code()` is `None`) mean the script itself failed to
+                // run rather than producing a verdict, so git aborts instead of recording
+                // Bad - narrowing a tooling hiccup forward would wrongly implicate every
+                // descendant commit with no way back short of restarting the bisection.
+                let raw_exit_code = commit_index_exit_code.1.code();
+                let exit_status = match raw_exit_code {
+                    Some(0) => Status::Good,
+                    Some(124) | Some(125) => Status::Skip,
+                    Some(code) if (1..125).contains(&code) => Status::Bad,
+                    _ => {
+                        eprintln!(
+                            "Aborting bisection: commit `{}` could not be tested.\n\
+                            {}\n\
+                            Exit codes 126/127, 128 and above, and termination by signal \
+                            indicate the script itself failed to run, not a real {old}/{new} \
+                            verdict, so git-biasect stops rather than guessing.\n\
+                            \n\
+                            Reproduce this failure with these commands:\n\
+                            {}\n\
+                            \n\
+                            Captured output (tail):\n\
+                            {}",
+                            commit_hash,
+                            match raw_exit_code {
+                                Some(code) => format!("Exit code: {code}."),
+                                None => format!(
+                                    "Terminated by signal {}.",
+                                    commit_index_exit_code.1.signal().unwrap()
+                                ),
+                            },
+                            reproducer_shell_commands(&run_opts.repo_path, &run_opts.script, commit_hash),
+                            last_cmd_out.tail(20),
+                            old = terms.old,
+                            new = terms.new,
+                        );
+                        let _ = worktree_prune(&run_opts.repo_path).wait();
+                        return Ok(());
+                    }
                 };
+                let exit_code = raw_exit_code.unwrap();
 
                 // Check if result is invalid
-                // TODO: Nicer error messages that allow users to reproduce the failure with example commands
                 if commit_index_exit_code.0 == 0 && exit_status == Status::Bad {
                     // The first commit must be good - that's what the user told us when setting up the bisection!
                     eprintln!(
                         "Initial bisection bounds invalid.\n\
-                        Commit: `{}` evaluated to bad with exit code {}.\n\
-                        The oldest commit must not be bad.\n\
+                        Commit: `{}` evaluated to {new} with exit code {}.\n\
+                        The oldest commit must not be {new}.\n\
                         \n\
                         Reproduce this failure with these commands:\n\
+                        {}\n\
+                        \n\
+                        Captured output (tail):\n\
                         {}",
                         commits.get(commit_index_exit_code.0).unwrap(),
                         exit_code,
@@ -192,7 +363,9 @@ fn main() -> Result<(), String> {
                             &run_opts.repo_path,
                             &run_opts.script,
                             &state.commits.get(commit_index_exit_code.0).unwrap().hash
-                        )
+                        ),
+                        last_cmd_out.tail(20),
+                        new = terms.new,
                     );
                     return Ok(());
                 } else if commit_index_exit_code.0 == commits.len() - 1
@@ -201,10 +374,13 @@ fn main() -> Result<(), String> {
                     // The last commit must be bad - that's what the user told us when setting up the bisection!
                     eprintln!(
                         "Initial bisection bounds invalid.\n\
-                        Commit: `{}` evaluated to good with exit code {}.\n\
-                        The newest commit must not be good.\n\
+                        Commit: `{}` evaluated to {old} with exit code {}.\n\
+                        The newest commit must not be {old}.\n\
                         \n\
                         Reproduce this failure with these commands:\n\
+                        {}\n\
+                        \n\
+                        Captured output (tail):\n\
                         {}",
                         commits.get(commit_index_exit_code.0).unwrap(),
                         exit_code,
@@ -212,7 +388,9 @@ fn main() -> Result<(), String> {
                             &run_opts.repo_path,
                             &run_opts.script,
                             &state.commits.get(commit_index_exit_code.0).unwrap().hash
-                        )
+                        ),
+                        last_cmd_out.tail(20),
+                        old = terms.old,
                     );
                     return Ok(());
                 }
@@ -225,21 +403,11 @@ fn main() -> Result<(), String> {
                 let current_runtime = start.elapsed().as_secs_f64();
 
                 let commit_runtime = current_runtime
-                    - *old_state
-                        .runners
-                        .start_times
-                        .get(
-                            old_state
-                                .runners
-                                .commits
-                                .iter()
-                                .enumerate()
-                                .filter(|(_, commit_idx)| commit_idx == &&commit_index_exit_code.0)
-                                .map(|(runner_idx, _)| runner_idx)
-                                .next()
-                                .unwrap(),
-                        )
-                        .unwrap();
+                    - runners
+                        .iter()
+                        .find(|runner| runner.commit_idx == commit_index_exit_code.0)
+                        .unwrap()
+                        .start_time;
 
                 (state, invalidated_runners, new_runners) = step::<BasicAllocator>(
                     &old_state,
@@ -259,58 +427,63 @@ fn main() -> Result<(), String> {
                         "Bounds newly validated, reporting commits {:?}",
                         state.commits
                     );
-                    bisect_report_all(&state.commits, &run_opts.repo_path);
+                    bisect_report_all(&state.commits, &run_opts.repo_path, &terms);
                 } else if bounds_validated(&state.commits, run_opts.reckless) {
                     // Report all bisection steps right away when bounds are validated
                     bisect_report(
                         &run_opts.repo_path,
                         &exit_status,
                         commits.get(commit_index_exit_code.0).unwrap(),
+                        &terms,
                     );
                 }
 
                 // Cancel invalidated tasks
                 // TODO: Clean up temp folders
-                let _ = old_state
-                    .runners
-                    .commits
-                    .iter()
-                    .filter(|commit_idx| invalidated_runners.contains(commit_idx))
-                    .map(|commit_idx| {
-                        let mut invalidated_runners = runners
-                            .iter_mut()
-                            .filter(|x| x.0 == *commit_idx)
-                            .collect::<Vec<_>>();
-
-                        for invalidated_runners in invalidated_runners.iter_mut() {
-                            // println!("Killing {}", invalidated_runners.0);
-                            let killed = invalidated_runners.1.kill();
-                            if killed.is_ok() {
-                                // println!("Successfully cancelled {}", invalidated_runners.0);
-                            } else {
-                                panic!("Failed to kill invalidated runner: {:?}", killed.err());
-                            }
-                        }
-                    })
-                    .collect::<Vec<_>>();
+                for runner in runners.iter().filter(|r| invalidated_runners.contains(&r.commit_idx)) {
+                    cancelled.insert(runner.commit_idx);
+                    kill_runner(runner.pid);
+                }
 
-                let e_runners = runners
+                let e_runners: Vec<Runner> = runners
                     .into_iter()
-                    .filter(|commit| {
-                        !(invalidated_runners.contains(&commit.0)
-                            || commit_index_exit_code.0 == commit.0)
+                    .filter(|runner| {
+                        !(invalidated_runners.contains(&runner.commit_idx)
+                            || commit_index_exit_code.0 == runner.commit_idx)
                     })
-                    .collect::<Vec<_>>();
+                    .collect();
 
                 let n_runners = start_runners(
                     &new_runners,
                     &commits,
                     &run_opts.repo_path,
                     &run_opts.script,
+                    &tx,
+                    current_runtime,
                 );
 
                 runners = e_runners.into_iter().chain(n_runners).collect();
 
+                if state.skip_exhausted() {
+                    for runner in &runners {
+                        kill_runner(runner.pid);
+                    }
+                    eprintln!(
+                        "We cannot bisect further: every remaining candidate commit in the \
+                        narrowed range was skipped.\n\
+                        The first {new} commit is one of:\n{}",
+                        state
+                            .suspects()
+                            .iter()
+                            .map(|commit| format!("  {}", commit.hash))
+                            .collect::<Vec<_>>()
+                            .join("\n"),
+                        new = terms.new,
+                    );
+                    let _ = worktree_prune(&run_opts.repo_path).wait();
+                    return Ok(());
+                }
+
                 if runners.is_empty() {
                     break;
                 }
@@ -324,14 +497,20 @@ fn main() -> Result<(), String> {
                     .collect::<Vec<_>>()
                     .as_slice(),
                 &state.runners.commits,
+                &terms,
             );
 
             let _ = worktree_prune(&run_opts.repo_path).wait();
         }
         SubCommands::Next(next_opts) => {
-            let commits = get_commits(&next_opts.repo_path)?;
+            let commits = get_commits(&next_opts.repo_path, false)?;
+            let parents = get_commit_parents(&next_opts.repo_path, &commits, false)?;
+            let terms = Terms {
+                old: next_opts.term_old.clone(),
+                new: next_opts.term_new.clone(),
+            };
 
-            let state = init(&commits, 1, next_opts.check_bounds);
+            let state = init(&commits, &parents, 1, next_opts.check_bounds);
 
             print_commits(
                 state
@@ -341,6 +520,7 @@ fn main() -> Result<(), String> {
                     .collect::<Vec<_>>()
                     .as_slice(),
                 &state.runners.commits,
+                &terms,
             );
         }
     }
@@ -0,0 +1,82 @@
+pub mod alloc;
+pub mod shell;
+pub mod visualize;
+
+/// A commit's bisection verdict.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Status {
+    Good,
+    Bad,
+    Skip,
+    Unknown,
+}
+
+/// A commit hash paired with its current bisection verdict.
+#[derive(Debug, Clone)]
+pub struct CommitState {
+    pub hash: String,
+    pub status: Status,
+}
+
+/// Vocabulary for the two bisection verdicts, mirroring git's
+/// `--term-old`/`--term-new`. Defaults to "good"/"bad"; renaming them lets a
+/// bisection search for when a property *appeared* read naturally (e.g.
+/// `old`/`new`) instead of implying a regression.
+#[derive(Debug, Clone)]
+pub struct Terms {
+    pub old: String,
+    pub new: String,
+}
+
+impl Default for Terms {
+    fn default() -> Self {
+        Terms {
+            old: "good".to_string(),
+            new: "bad".to_string(),
+        }
+    }
+}
+
+impl Terms {
+    /// The configured word for a verdict; `Skip`/`Unknown` aren't renamed.
+    pub fn label(&self, status: Status) -> &str {
+        match status {
+            Status::Good => &self.old,
+            Status::Bad => &self.new,
+            Status::Skip => "skip",
+            Status::Unknown => "unknown",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn label_uses_default_good_bad_vocabulary() {
+        let terms = Terms::default();
+        assert_eq!(terms.label(Status::Good), "good");
+        assert_eq!(terms.label(Status::Bad), "bad");
+    }
+
+    #[test]
+    fn label_uses_renamed_vocabulary() {
+        let terms = Terms {
+            old: "old".to_string(),
+            new: "new".to_string(),
+        };
+        assert_eq!(terms.label(Status::Good), "old");
+        assert_eq!(terms.label(Status::Bad), "new");
+    }
+
+    #[test]
+    fn label_never_renames_skip_or_unknown() {
+        let terms = Terms {
+            old: "old".to_string(),
+            new: "new".to_string(),
+        };
+        assert_eq!(terms.label(Status::Skip), "skip");
+        assert_eq!(terms.label(Status::Unknown), "unknown");
+    }
+}
@@ -0,0 +1,296 @@
+use crate::{Status, Terms};
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+
+fn git_stdout(repo_path: &Path, args: &[&str]) -> Result<String, String> {
+    let output = Command::new("git")
+        .current_dir(repo_path)
+        .args(args)
+        .output()
+        .map_err(|e| format!("failed to run `git {}`: {e}", args.join(" ")))?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "`git {}` failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+fn rev_parse(repo_path: &Path, rev: &str) -> Result<String, String> {
+    git_stdout(repo_path, &["rev-parse", rev])
+}
+
+/// Resolve the ordered list of commit hashes between the bisection's good and
+/// bad refs, oldest first, so index `0` is the known-good commit and the last
+/// index is the known-bad commit. With `first_parent` set, only the
+/// first-parent chain of the bad commit is walked, mirroring git's
+/// `FIND_BISECTION_FIRST_PARENT_ONLY` so bisection pinpoints which merge
+/// introduced a regression rather than diving into feature-branch commits.
+pub fn get_commits(repo_path: &Path, first_parent: bool) -> Result<Vec<String>, String> {
+    let bad = rev_parse(repo_path, "refs/bisect/bad")?;
+    let good = rev_parse(repo_path, "refs/bisect/good")?;
+    let range = format!("{good}..{bad}");
+
+    let mut args = vec!["rev-list", "--reverse"];
+    if first_parent {
+        args.push("--first-parent");
+    }
+    args.push(&range);
+
+    let out = git_stdout(repo_path, &args)?;
+    let mut commits = vec![good];
+    commits.extend(out.lines().map(str::to_owned));
+    Ok(commits)
+}
+
+/// Parent indices (within `commits`) for each commit, built from
+/// `git rev-list --parents`. Parents outside the `good..bad` range are
+/// dropped, since the allocator only reasons about commits still inside the
+/// bisection window. `first_parent` must match the value passed to
+/// [`get_commits`] so the resulting edges stay consistent with `commits`.
+pub fn get_commit_parents(
+    repo_path: &Path,
+    commits: &[String],
+    first_parent: bool,
+) -> Result<Vec<Vec<usize>>, String> {
+    let good = commits.first().ok_or("no commits to bisect")?;
+    let bad = commits.last().ok_or("no commits to bisect")?;
+    let range = format!("{good}..{bad}");
+
+    let mut args = vec!["rev-list", "--reverse", "--parents"];
+    if first_parent {
+        args.push("--first-parent");
+    }
+    args.push(&range);
+
+    let out = git_stdout(repo_path, &args)?;
+
+    let index_of: HashMap<&str, usize> = commits
+        .iter()
+        .enumerate()
+        .map(|(i, hash)| (hash.as_str(), i))
+        .collect();
+
+    let mut parents = vec![Vec::new(); commits.len()];
+    for line in out.lines() {
+        let mut parts = line.split_whitespace();
+        let Some(hash) = parts.next() else {
+            continue;
+        };
+        let Some(&idx) = index_of.get(hash) else {
+            continue;
+        };
+        parents[idx] = parts.filter_map(|p| index_of.get(p).copied()).collect();
+    }
+
+    Ok(parents)
+}
+
+/// Whether `ancestor` is reachable from `descendant`, via `git merge-base --is-ancestor`.
+pub fn is_ancestor(repo_path: &Path, ancestor: &str, descendant: &str) -> Result<bool, String> {
+    let status = Command::new("git")
+        .current_dir(repo_path)
+        .args(["merge-base", "--is-ancestor", ancestor, descendant])
+        .status()
+        .map_err(|e| format!("failed to run `git merge-base --is-ancestor`: {e}"))?;
+    Ok(status.success())
+}
+
+/// All merge bases of `a` and `b`, via `git merge-base --all`.
+pub fn merge_base(repo_path: &Path, a: &str, b: &str) -> Result<Vec<String>, String> {
+    let out = git_stdout(repo_path, &["merge-base", "--all", a, b])?;
+    Ok(out.lines().map(str::to_owned).collect())
+}
+
+pub fn get_commit_files(repo_path: &Path, hash: &str) -> Result<Vec<String>, String> {
+    let out = git_stdout(repo_path, &["show", "--name-only", "--pretty=format:", hash])?;
+    Ok(out.lines().filter(|l| !l.is_empty()).map(str::to_owned).collect())
+}
+
+fn worktree_dir(repo_path: &Path, commit: &str) -> PathBuf {
+    repo_path.join(".git-biasect").join("worktrees").join(commit)
+}
+
+fn log_dir(repo_path: &Path, commit: &str) -> PathBuf {
+    repo_path.join(".git-biasect").join("logs").join(commit)
+}
+
+/// A captured invocation of the bisection script against one commit: the
+/// command, where it ran, its exit code, and the stdout/stderr it produced.
+/// Used both for attaching failure context to error messages and for the
+/// verbose per-step log.
+pub struct CmdOut {
+    pub command: String,
+    pub cwd: PathBuf,
+    pub exit_code: Option<i32>,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+impl CmdOut {
+    /// Last `n` lines of stdout followed by stderr, for compact error messages.
+    pub fn tail(&self, n: usize) -> String {
+        let mut lines: Vec<&str> = self.stdout.lines().chain(self.stderr.lines()).collect();
+        if lines.len() > n {
+            lines = lines.split_off(lines.len() - n);
+        }
+        lines.join("\n")
+    }
+}
+
+impl fmt::Display for CmdOut {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "$ {} (in {})", self.command, self.cwd.display())?;
+        match self.exit_code {
+            Some(code) => writeln!(f, "exit code: {code}")?,
+            None => writeln!(f, "exit code: (terminated by signal)")?,
+        }
+        if !self.stdout.is_empty() {
+            writeln!(f, "--- stdout ---\n{}", self.stdout)?;
+        }
+        if !self.stderr.is_empty() {
+            writeln!(f, "--- stderr ---\n{}", self.stderr)?;
+        }
+        Ok(())
+    }
+}
+
+/// Spawn the bisection script against `commit`, running it inside a
+/// dedicated worktree so concurrent runners never step on each other.
+/// Stdout/stderr are redirected to per-commit log files rather than
+/// inherited, so [`read_cmd_out`] can surface why a run failed.
+pub fn run_script(repo_path: &Path, script: &str, commit: &str) -> Child {
+    let worktree_dir = worktree_dir(repo_path, commit);
+
+    Command::new("git")
+        .current_dir(repo_path)
+        .arg("worktree")
+        .arg("add")
+        .arg("--force")
+        .arg("--detach")
+        .arg(&worktree_dir)
+        .arg(commit)
+        .status()
+        .expect("failed to create worktree");
+
+    let log_dir = log_dir(repo_path, commit);
+    fs::create_dir_all(&log_dir).expect("failed to create log directory");
+    let stdout_log = File::create(log_dir.join("stdout.log")).expect("failed to create stdout log");
+    let stderr_log = File::create(log_dir.join("stderr.log")).expect("failed to create stderr log");
+
+    Command::new("sh")
+        .arg("-c")
+        .arg(script)
+        .current_dir(&worktree_dir)
+        .stdout(Stdio::from(stdout_log))
+        .stderr(Stdio::from(stderr_log))
+        .spawn()
+        .expect("failed to spawn runner script")
+}
+
+/// Read back a finished runner's captured output, bundled with its exit
+/// status, for error reporting and verbose per-step logging.
+pub fn read_cmd_out(repo_path: &Path, script: &str, commit: &str, exit_code: Option<i32>) -> CmdOut {
+    let dir = log_dir(repo_path, commit);
+    CmdOut {
+        command: script.to_string(),
+        cwd: worktree_dir(repo_path, commit),
+        exit_code,
+        stdout: fs::read_to_string(dir.join("stdout.log")).unwrap_or_default(),
+        stderr: fs::read_to_string(dir.join("stderr.log")).unwrap_or_default(),
+    }
+}
+
+/// The vocabulary actually registered with the running `git bisect` session
+/// (`git bisect terms`), so a configured `--term-old`/`--term-new` can be
+/// validated against it before any verdict is reported: `bisect_report` runs
+/// `git bisect <verb> <hash>` using the configured words, and that fails
+/// outright if they don't match what `git bisect start` was given.
+pub fn bisect_terms(repo_path: &Path) -> Result<Terms, String> {
+    Ok(Terms {
+        old: git_stdout(repo_path, &["bisect", "terms", "--term-good"])?,
+        new: git_stdout(repo_path, &["bisect", "terms", "--term-bad"])?,
+    })
+}
+
+pub fn bisect_report(repo_path: &Path, status: &Status, hash: &str, terms: &Terms) {
+    if *status == Status::Unknown {
+        return;
+    }
+    let verb = terms.label(*status);
+
+    let _ = Command::new("git")
+        .current_dir(repo_path)
+        .args(["bisect", verb, hash])
+        .status();
+}
+
+/// Shell commands a user can paste to reproduce a runner's verdict by hand.
+pub fn reproducer_shell_commands(repo_path: &Path, script: &str, hash: &str) -> String {
+    format!(
+        "  git -C {} worktree add --force --detach /tmp/git-biasect-repro {}\n  (cd /tmp/git-biasect-repro && {})",
+        repo_path.display(),
+        hash,
+        script
+    )
+}
+
+pub fn worktree_prune(repo_path: &Path) -> Child {
+    Command::new("git")
+        .current_dir(repo_path)
+        .args(["worktree", "prune"])
+        .spawn()
+        .expect("failed to spawn `git worktree prune`")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cmd_out(stdout: &str, stderr: &str) -> CmdOut {
+        CmdOut {
+            command: "echo".to_string(),
+            cwd: PathBuf::from("/tmp"),
+            exit_code: Some(0),
+            stdout: stdout.to_string(),
+            stderr: stderr.to_string(),
+        }
+    }
+
+    #[test]
+    fn tail_returns_everything_when_n_exceeds_line_count() {
+        let out = cmd_out("a\nb\nc", "");
+        assert_eq!(out.tail(10), "a\nb\nc");
+    }
+
+    #[test]
+    fn tail_truncates_to_last_n_lines() {
+        let out = cmd_out("a\nb\nc\nd", "");
+        assert_eq!(out.tail(2), "c\nd");
+    }
+
+    #[test]
+    fn tail_covers_stdout_only() {
+        let out = cmd_out("a\nb", "");
+        assert_eq!(out.tail(5), "a\nb");
+    }
+
+    #[test]
+    fn tail_covers_stderr_only() {
+        let out = cmd_out("", "x\ny");
+        assert_eq!(out.tail(5), "x\ny");
+    }
+
+    #[test]
+    fn tail_joins_stdout_then_stderr_before_truncating() {
+        let out = cmd_out("a\nb", "c\nd");
+        assert_eq!(out.tail(2), "c\nd");
+    }
+}
@@ -0,0 +1,28 @@
+use crate::{Status, Terms};
+
+/// Render a one-line progress bar of commit verdicts, marking commits that
+/// currently have a runner assigned. Good/bad markers are the first letter
+/// of the configured `terms`, so renamed vocabularies still read naturally.
+pub fn print_commits(statuses: &[Status], runner_commits: &[usize], terms: &Terms) {
+    let old_mark = terms.old.chars().next().unwrap_or('o').to_ascii_uppercase();
+    let new_mark = terms.new.chars().next().unwrap_or('n').to_ascii_uppercase();
+
+    let line: String = statuses
+        .iter()
+        .enumerate()
+        .map(|(i, status)| {
+            if runner_commits.contains(&i) {
+                'R'
+            } else {
+                match status {
+                    Status::Good => old_mark,
+                    Status::Bad => new_mark,
+                    Status::Skip => 'S',
+                    Status::Unknown => '.',
+                }
+            }
+        })
+        .collect();
+
+    println!("{line}");
+}